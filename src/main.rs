@@ -10,6 +10,18 @@
 //! - **Daemon Mode**: Run as a background daemon with `-d` flag
 //! - **Async Processing**: Built with Tokio and Actix-Web for high concurrency
 //! - **KRaft Support**: Compatible with modern Kafka without Zookeeper
+//! - **Request/Reply Mode**: Endpoints can optionally block for a correlated Kafka reply,
+//!   turning the collector into a synchronous HTTP-over-Kafka gateway
+//! - **Schema Validation**: Endpoints can require request bodies to satisfy a JSON Schema
+//!   before anything is forwarded to Kafka
+//! - **Header Forwarding & Keying**: Endpoints can copy an allowlist of HTTP headers onto
+//!   Kafka record headers and derive the record key from a header or the request body
+//! - **Delivery Modes**: Configure idempotent (`reliable`) or fully transactional
+//!   (`transactional`) production for exactly-once delivery guarantees
+//! - **Content-Based Routing**: An endpoint can fan out to different topics/partitions based
+//!   on a header or body field, via a configurable dispatch table
+//! - **Replay/Drain**: Optional GET endpoints tail a Kafka topic back out over HTTP, useful
+//!   for debugging the same topics the collector feeds
 //!
 //! ## Configuration
 //!
@@ -29,15 +41,240 @@
 //! ```
 
 use actix_web::{web, App, HttpResponse, HttpServer};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use clap::Parser;
 use daemonize::Daemonize;
+use dashmap::DashMap;
 use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::{Headers, Message, OwnedHeaders};
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use rdkafka::topic_partition_list::{Offset, TopicPartitionList};
+use jsonschema::JSONSchema;
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// Header name used to correlate a Kafka reply with the request that triggered it.
+const CORRELATION_ID_HEADER: &str = "correlation-id";
+
+/// Map of correlation id -> the oneshot sender waiting on the matching reply.
+///
+/// Shared between the HTTP handlers (which register a sender before producing) and the
+/// per-reply-topic consumer tasks (which resolve it once a matching reply arrives).
+type PendingReplies = Arc<DashMap<String, oneshot::Sender<Vec<u8>>>>;
+
+/// A JSON Schema compiled once at startup and kept for the lifetime of the process.
+///
+/// The backing `serde_json::Value` is intentionally leaked (see [`compile_schema`]) so the
+/// compiled validator can be `'static` and shared across worker threads via `Arc`.
+type CompiledSchema = JSONSchema<'static>;
+
+/// Load and compile the JSON Schema at `path`.
+///
+/// The parsed schema document is leaked to give the compiled validator a `'static` lifetime;
+/// this is a one-time startup cost per configured schema, not a per-request leak.
+fn compile_schema(path: &str) -> Result<CompiledSchema, Box<dyn std::error::Error>> {
+    let file = File::open(path).map_err(|e| format!("Failed to open schema {}: {}", path, e))?;
+    let schema: serde_json::Value =
+        serde_json::from_reader(file).map_err(|e| format!("Failed to parse schema {}: {}", path, e))?;
+    let schema: &'static serde_json::Value = Box::leak(Box::new(schema));
+    JSONSchema::compile(schema).map_err(|e| format!("Invalid JSON schema {}: {}", path, e).into())
+}
+
+/// Optional SASL/SSL settings applied to every Kafka client (producer and consumers) this
+/// process creates. Unset fields leave librdkafka on its plaintext defaults.
+#[derive(Debug, Clone, Default)]
+struct KafkaSecurityConfig {
+    security_protocol: Option<String>,
+    sasl_mechanism: Option<String>,
+    sasl_username: Option<String>,
+    sasl_password: Option<String>,
+    ssl_ca_location: Option<String>,
+    ssl_certificate_location: Option<String>,
+    ssl_key_location: Option<String>,
+    ssl_key_password: Option<String>,
+}
+
+impl KafkaSecurityConfig {
+    /// Read settings from the `KAFKA_SECURITY_PROTOCOL`, `KAFKA_SASL_*`, and `KAFKA_SSL_*`
+    /// environment variables documented on [`main`].
+    fn from_env() -> Self {
+        Self {
+            security_protocol: std::env::var("KAFKA_SECURITY_PROTOCOL").ok(),
+            sasl_mechanism: std::env::var("KAFKA_SASL_MECHANISM").ok(),
+            sasl_username: std::env::var("KAFKA_SASL_USERNAME").ok(),
+            sasl_password: std::env::var("KAFKA_SASL_PASSWORD").ok(),
+            ssl_ca_location: std::env::var("KAFKA_SSL_CA_LOCATION").ok(),
+            ssl_certificate_location: std::env::var("KAFKA_SSL_CERTIFICATE_LOCATION").ok(),
+            ssl_key_location: std::env::var("KAFKA_SSL_KEY_LOCATION").ok(),
+            ssl_key_password: std::env::var("KAFKA_SSL_KEY_PASSWORD").ok(),
+        }
+    }
+
+    /// Apply these settings onto `client_config`, leaving anything unset untouched.
+    fn apply(&self, client_config: &mut ClientConfig) {
+        if let Some(v) = &self.security_protocol {
+            client_config.set("security.protocol", v);
+        }
+        if let Some(v) = &self.sasl_mechanism {
+            client_config.set("sasl.mechanisms", v);
+        }
+        if let Some(v) = &self.sasl_username {
+            client_config.set("sasl.username", v);
+        }
+        if let Some(v) = &self.sasl_password {
+            client_config.set("sasl.password", v);
+        }
+        if let Some(v) = &self.ssl_ca_location {
+            client_config.set("ssl.ca.location", v);
+        }
+        if let Some(v) = &self.ssl_certificate_location {
+            client_config.set("ssl.certificate.location", v);
+        }
+        if let Some(v) = &self.ssl_key_location {
+            client_config.set("ssl.key.location", v);
+        }
+        if let Some(v) = &self.ssl_key_password {
+            client_config.set("ssl.key.password", v);
+        }
+    }
+}
+
+/// Validate `body` as JSON against `validator`.
+///
+/// Returns `None` when the body is valid JSON that satisfies the schema (the caller should
+/// proceed to forward it). Otherwise returns `Some(response)` with an HTTP 400: for a schema
+/// violation its `errors` field is a JSON array of `{path, message}` validation errors; for a
+/// body that isn't valid JSON its `error` field holds the parse error instead.
+fn validate_body(validator: &CompiledSchema, body: &web::Bytes) -> Option<HttpResponse> {
+    let instance: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => {
+            return Some(HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": "Request body is not valid JSON",
+                "error": e.to_string()
+            })));
+        }
+    };
+
+    if let Err(errors) = validator.validate(&instance) {
+        let errors: Vec<_> = errors
+            .map(|e| {
+                serde_json::json!({
+                    "path": e.instance_path.to_string(),
+                    "message": e.to_string()
+                })
+            })
+            .collect();
+        return Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "Schema validation failed",
+            "errors": errors
+        })));
+    }
+
+    None
+}
+
+/// Copy the values of any headers named in `allowlist` that are present on `req` onto a new
+/// set of Kafka record headers, in allowlist order. Headers missing from the request, or whose
+/// value isn't valid UTF-8, are skipped.
+fn forward_headers(req: &actix_web::HttpRequest, allowlist: &[String]) -> OwnedHeaders {
+    let mut headers = OwnedHeaders::new();
+    for name in allowlist {
+        if let Some(value) = req.headers().get(name.as_str()).and_then(|v| v.to_str().ok()) {
+            headers = headers.insert(rdkafka::message::Header {
+                key: name,
+                value: Some(value.as_bytes()),
+            });
+        }
+    }
+    headers
+}
+
+/// The outcome of resolving an endpoint's content-based route for a single request.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum RouteResolution {
+    /// The endpoint has no `route_by` configured; use its fixed topic/partition.
+    Disabled,
+    /// Route to this topic/partition instead of the endpoint's fixed one.
+    Target(String, i32),
+    /// The observed routing value matched no entry in `routes` and no `default_route` is set.
+    Unmatched,
+}
+
+/// Resolve which topic/partition a request should be routed to, per the endpoint's
+/// `route_by`/`routes`/`default_route` configuration.
+///
+/// The routing value is read from the HTTP header named by `route_by` if present, falling
+/// back to the top-level JSON body field of the same name.
+fn resolve_route(
+    req: &actix_web::HttpRequest,
+    body: &[u8],
+    route_by: &Option<String>,
+    routes: &Option<Arc<HashMap<String, RouteTarget>>>,
+    default_route: &Option<RouteTarget>,
+) -> RouteResolution {
+    let Some(route_by) = route_by else {
+        return RouteResolution::Disabled;
+    };
+
+    let value = req
+        .headers()
+        .get(route_by.as_str())
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            let body_json: serde_json::Value = serde_json::from_slice(body).ok()?;
+            let value = body_json.get(route_by)?;
+            Some(match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        });
+
+    let target = value
+        .as_deref()
+        .and_then(|v| routes.as_ref().and_then(|routes| routes.get(v)))
+        .or(default_route.as_ref());
+
+    match target {
+        Some(target) => RouteResolution::Target(target.kafka_topic.clone(), target.kafka_partition),
+        None => RouteResolution::Unmatched,
+    }
+}
+
+/// Derive the Kafka record key for this request from the endpoint's configured `key_source`.
+///
+/// Returns `None` when no key source is configured (the caller should fall back to the
+/// endpoint's fixed partition), or when the configured source has no value for this request.
+fn derive_key(req: &actix_web::HttpRequest, body: &[u8], key_source: &Option<KeySource>) -> Option<String> {
+    match key_source.as_ref()? {
+        KeySource::Header(name) => req.headers().get(name.as_str()).and_then(|v| v.to_str().ok()).map(str::to_string),
+        KeySource::JsonPointer(pointer) => {
+            let body_json: serde_json::Value = serde_json::from_slice(body).ok()?;
+            let value = body_json.pointer(pointer)?;
+            Some(match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        }
+    }
+}
+
+/// Look up the `correlation-id` header on a Kafka message's headers, used to match a reply
+/// against the request that's waiting for it in `pending_replies`.
+fn find_correlation_id<H: Headers>(headers: Option<&H>) -> Option<String> {
+    headers
+        .and_then(|headers| headers.iter().find(|h| h.key == CORRELATION_ID_HEADER).and_then(|h| h.value))
+        .map(|value| String::from_utf8_lossy(value).into_owned())
+}
 
 /// Command line arguments for the Kafka Collector
 #[derive(Parser, Debug)]
@@ -76,6 +313,56 @@ struct EndpointConfig {
     kafka_topic: String,
     /// The Kafka partition to send messages to
     kafka_partition: i32,
+    /// Topic to await a correlated reply on, turning this endpoint into a synchronous
+    /// request/reply gateway instead of a fire-and-forget forwarder.
+    #[serde(default)]
+    reply_topic: Option<String>,
+    /// How long to wait for a reply before responding with HTTP 504. Only meaningful when
+    /// `reply_topic` is set; defaults to 5000ms.
+    #[serde(default)]
+    reply_timeout_ms: Option<u64>,
+    /// Path to a JSON Schema file. When set, request bodies are validated against it before
+    /// being forwarded to Kafka; invalid bodies are rejected with HTTP 400.
+    #[serde(default)]
+    schema: Option<String>,
+    /// Allowlist of HTTP header names to copy onto the outgoing Kafka record as record headers.
+    #[serde(default)]
+    forward_headers: Option<Vec<String>>,
+    /// Where to derive the Kafka record key from. When a key is found for a request,
+    /// `kafka_partition` is ignored and librdkafka's default key-hash partitioner distributes the
+    /// record instead, so records sharing a key stay in order. A request with no value at the
+    /// configured source is sent with an empty key to `kafka_partition`.
+    #[serde(default)]
+    key_source: Option<KeySource>,
+    /// HTTP header name or top-level JSON body field to read the routing value from. Requires
+    /// `routes` to be configured; the header is checked first, then the body field.
+    #[serde(default)]
+    route_by: Option<String>,
+    /// Maps observed `route_by` values to the Kafka topic/partition that value routes to.
+    #[serde(default)]
+    routes: Option<HashMap<String, RouteTarget>>,
+    /// Target used when the observed `route_by` value matches no entry in `routes`. Without
+    /// one, unmatched values are rejected with HTTP 422.
+    #[serde(default)]
+    default_route: Option<RouteTarget>,
+}
+
+/// A Kafka topic/partition pair that a `route_by` value can dispatch to.
+#[derive(Debug, Deserialize, Clone)]
+struct RouteTarget {
+    /// The Kafka topic to send messages to
+    kafka_topic: String,
+    /// The Kafka partition to send messages to
+    kafka_partition: i32,
+}
+
+/// Where an endpoint derives its Kafka record key from.
+#[derive(Debug, Deserialize, Clone)]
+enum KeySource {
+    /// Use the value of a named HTTP request header.
+    Header(String),
+    /// Use the value at a JSON pointer (RFC 6901) into the request body.
+    JsonPointer(String),
 }
 
 /// Top-level configuration structure loaded from conf.yaml
@@ -83,6 +370,29 @@ struct EndpointConfig {
 struct Config {
     /// List of endpoints to configure
     endpoints: Vec<EndpointConfig>,
+    /// List of replay/drain consumers to expose as GET endpoints
+    #[serde(default)]
+    consumers: Vec<ConsumerConfig>,
+}
+
+/// Configuration for an HTTP GET endpoint that drains messages back out of a Kafka topic, for
+/// replaying or tailing the same topics the collector feeds.
+#[derive(Debug, Deserialize, Clone)]
+struct ConsumerConfig {
+    /// The HTTP GET path for this consumer (e.g., "/api/v1/events/replay")
+    path: String,
+    /// The Kafka topic to read from
+    kafka_topic: String,
+    /// Client id passed to librdkafka as `group.id` when creating the replay consumer.
+    ///
+    /// This is a required librdkafka client setting, not a real consumer group: every request
+    /// assigns partitions directly with `assign()` and replays the topic from the beginning, so
+    /// no offsets are committed or tracked against it across calls.
+    client_id: String,
+    /// Maximum number of messages to return in one request
+    max_messages: usize,
+    /// How long to keep polling for more messages before returning whatever was collected
+    poll_timeout_ms: u64,
 }
 
 /// Health check handler that returns a simple OK response
@@ -115,6 +425,15 @@ async fn health_check() -> HttpResponse {
 /// - `KAFKA_DELIVERY_TIMEOUT_MS`: Delivery timeout in ms (default: 5000)
 /// - `KAFKA_REQUEST_TIMEOUT_MS`: Request timeout in ms (default: 5000)
 /// - `KAFKA_SOCKET_TIMEOUT_MS`: Socket timeout in ms (default: 5000)
+/// - `KAFKA_SECURITY_PROTOCOL`: librdkafka `security.protocol`, e.g. `SASL_SSL` (default: unset, plaintext)
+/// - `KAFKA_SASL_MECHANISM`: librdkafka `sasl.mechanisms`, e.g. `PLAIN` or `SCRAM-SHA-512`
+/// - `KAFKA_SASL_USERNAME` / `KAFKA_SASL_PASSWORD`: SASL credentials
+/// - `KAFKA_SSL_CA_LOCATION`: Path to the CA certificate used to verify the broker
+/// - `KAFKA_SSL_CERTIFICATE_LOCATION` / `KAFKA_SSL_KEY_LOCATION`: Client certificate/key for mutual TLS
+/// - `KAFKA_SSL_KEY_PASSWORD`: Passphrase for the client key, if encrypted
+/// - `KAFKA_DELIVERY_MODE`: `fire_and_forget` (default), `reliable` (idempotent producer,
+///   `acks=all`), or `transactional` (reliable plus a Kafka transaction per request)
+/// - `KAFKA_TRANSACTIONAL_ID`: Required when `KAFKA_DELIVERY_MODE=transactional`
 /// - `PUBLIC_ACCESS`: Set to "true" for public access (changes host to 0.0.0.0)
 /// - `RUST_LOG`: Log level (handled by env_logger)
 ///
@@ -164,6 +483,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let request_timeout = std::env::var("KAFKA_REQUEST_TIMEOUT_MS").unwrap_or_else(|_| "5000".to_string());
     let socket_timeout = std::env::var("KAFKA_SOCKET_TIMEOUT_MS").unwrap_or_else(|_| "5000".to_string());
 
+    // Optional SASL/SSL settings for secured clusters, applied to every Kafka client (producer
+    // and consumers) this process creates; unset vars leave librdkafka on its plaintext defaults.
+    let kafka_security = KafkaSecurityConfig::from_env();
+
+    let delivery_mode = std::env::var("KAFKA_DELIVERY_MODE").unwrap_or_else(|_| "fire_and_forget".to_string());
+    let transactional_id = std::env::var("KAFKA_TRANSACTIONAL_ID").ok();
+    let transactional = delivery_mode == "transactional";
+    if !matches!(delivery_mode.as_str(), "fire_and_forget" | "reliable" | "transactional") {
+        return Err(format!(
+            "Invalid KAFKA_DELIVERY_MODE: {} (expected fire_and_forget, reliable, or transactional)",
+            delivery_mode
+        )
+        .into());
+    }
+
     log::info!("Loading configuration from {}", args.config);
     let config: Config = match std::fs::File::open(&args.config) {
         Ok(file) => serde_yaml::from_reader(file)?,
@@ -173,8 +507,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    // Validate configuration: ensure paths are unique and not empty
+    // Validate configuration: ensure paths are unique and not empty, and compile any
+    // per-endpoint JSON schemas up front so bad schemas fail fast at startup.
     let mut paths = HashSet::new();
+    let mut compiled_schemas: HashMap<String, Arc<CompiledSchema>> = HashMap::new();
     for endpoint in &config.endpoints {
         if endpoint.path.is_empty() {
             return Err("Endpoint path cannot be empty".into());
@@ -185,20 +521,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if !paths.insert(&endpoint.path) {
             return Err(format!("Duplicate path: {}", endpoint.path).into());
         }
+        if let Some(schema_path) = &endpoint.schema {
+            log::info!("Compiling schema {} for path {}", schema_path, endpoint.path);
+            let schema = compile_schema(schema_path)
+                .map_err(|e| format!("Failed to compile schema for path {}: {}", endpoint.path, e))?;
+            compiled_schemas.insert(endpoint.path.clone(), Arc::new(schema));
+        }
+    }
+
+    // Validate the replay/drain consumers the same way: unique, non-empty paths.
+    let mut consumer_paths = HashSet::new();
+    for consumer in &config.consumers {
+        if consumer.path.is_empty() {
+            return Err("Consumer path cannot be empty".into());
+        }
+        if consumer.kafka_topic.is_empty() {
+            return Err(format!("Kafka topic for consumer path {} cannot be empty", consumer.path).into());
+        }
+        if !consumer_paths.insert(&consumer.path) {
+            return Err(format!("Duplicate consumer path: {}", consumer.path).into());
+        }
     }
 
     log::info!("Creating Kafka producer with bootstrap servers: {}", kafka_servers);
-    let producer: FutureProducer = ClientConfig::new()
+    let mut producer_config = ClientConfig::new();
+    producer_config
         .set("bootstrap.servers", &kafka_servers)
         .set("message.timeout.ms", &message_timeout)
         .set("delivery.timeout.ms", &delivery_timeout)
         .set("request.timeout.ms", &request_timeout)
-        .set("socket.timeout.ms", &socket_timeout)
-        .create()
-        .map_err(|e| {
-            log::error!("Failed to create Kafka producer: {}", e);
-            format!("Kafka producer creation failed: {}", e)
-        })?;
+        .set("socket.timeout.ms", &socket_timeout);
+
+    if let Some(protocol) = &kafka_security.security_protocol {
+        log::info!("Using Kafka security.protocol: {}", protocol);
+    }
+    kafka_security.apply(&mut producer_config);
+
+    if delivery_mode == "reliable" || delivery_mode == "transactional" {
+        log::info!("Using {} delivery mode: enabling idempotence and acks=all", delivery_mode);
+        producer_config.set("enable.idempotence", "true").set("acks", "all");
+    }
+    if transactional {
+        let transactional_id = transactional_id
+            .as_deref()
+            .ok_or("KAFKA_TRANSACTIONAL_ID must be set when KAFKA_DELIVERY_MODE=transactional")?;
+        producer_config.set("transactional.id", transactional_id);
+    }
+
+    let producer: FutureProducer = producer_config.create().map_err(|e| {
+        log::error!("Failed to create Kafka producer: {}", e);
+        if matches!(&kafka_security.security_protocol, Some(p) if p.contains("SSL") || p.contains("SASL")) {
+            log::error!(
+                "security.protocol={} requires librdkafka to be built with the ssl/gssapi features; \
+                 verify the linked librdkafka supports it",
+                kafka_security.security_protocol.as_deref().unwrap_or("")
+            );
+        }
+        format!("Kafka producer creation failed: {}", e)
+    })?;
+
+    if transactional {
+        log::info!("Initializing Kafka transactions for transactional.id={}", transactional_id.as_deref().unwrap_or(""));
+        producer
+            .init_transactions(Duration::from_secs(10))
+            .map_err(|e| format!("Failed to initialize Kafka transactions: {}", e))?;
+    }
 
     // Test Kafka connection by getting metadata
     log::info!("Testing Kafka connection...");
@@ -222,17 +609,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let producer = Arc::new(producer);
     let endpoints = config.endpoints;
+    let consumers = config.consumers;
+
+    // Registry of in-flight request/reply correlation ids, shared by every handler and by
+    // the reply consumer tasks spawned below.
+    let pending_replies: PendingReplies = Arc::new(DashMap::new());
+
+    // librdkafka's transactional producer API is a single state machine: begin/send/commit
+    // must never be interleaved across concurrent callers of the one shared producer. This
+    // lock single-flights the whole begin..commit/abort section when transactional mode is on.
+    let transaction_lock: Arc<tokio::sync::Mutex<()>> = Arc::new(tokio::sync::Mutex::new(()));
+
+    // Unique per-process id mixed into each reply consumer's group id. pending_replies is an
+    // in-process map, so if two collector instances shared a group id, Kafka would split the
+    // reply topic's partitions across them and a reply landing on the other instance's partition
+    // would never reach the oneshot that's waiting for it here. Giving every process its own
+    // group keeps each instance subscribed to every partition of its own reply topics.
+    let instance_id = Uuid::new_v4();
+
+    // Spawn one reply consumer per distinct reply_topic referenced by the endpoints, so a
+    // group of endpoints sharing a reply topic don't each open their own consumer.
+    let mut reply_topics: HashSet<String> = HashSet::new();
+    let mut reply_consumers_ready = Vec::new();
+    for endpoint in &endpoints {
+        if let Some(reply_topic) = &endpoint.reply_topic {
+            if reply_topics.insert(reply_topic.clone()) {
+                reply_consumers_ready.push(spawn_reply_consumer(
+                    &kafka_servers,
+                    &kafka_security,
+                    reply_topic.clone(),
+                    instance_id,
+                    Arc::clone(&pending_replies),
+                )?);
+            }
+        }
+    }
+
+    // Don't accept HTTP traffic on reply-mode endpoints until every reply consumer has joined
+    // its group; otherwise an early reply could land on a partition the consumer isn't assigned
+    // to yet and be silently unobservable. See `spawn_reply_consumer`.
+    for ready in reply_consumers_ready {
+        let _ = ready.await;
+    }
 
     // Create endpoint handlers outside the HttpServer closure to avoid duplication
     let mut endpoint_handlers = Vec::new();
     for endpoint in &endpoints {
-        let path = endpoint.path.clone();
-        let topic = endpoint.kafka_topic.clone();
-        let partition = endpoint.kafka_partition;
+        log::info!(
+            "Registering endpoint: {} -> topic: {}, partition: {}{}{}",
+            endpoint.path,
+            endpoint.kafka_topic,
+            endpoint.kafka_partition,
+            endpoint
+                .reply_topic
+                .as_ref()
+                .map(|t| format!(" (reply topic: {})", t))
+                .unwrap_or_default(),
+            endpoint.schema.as_ref().map(|s| format!(" (schema: {})", s)).unwrap_or_default()
+        );
 
-        log::info!("Registering endpoint: {} -> topic: {}, partition: {}", path, topic, partition);
+        let validator = compiled_schemas.get(&endpoint.path).cloned();
+        let routes = endpoint.routes.clone().map(Arc::new);
+        endpoint_handlers.push((endpoint.clone(), validator, routes));
+    }
 
-        endpoint_handlers.push((path, topic, partition));
+    for consumer in &consumers {
+        log::info!(
+            "Registering replay consumer: {} -> topic: {}, client id: {}",
+            consumer.path, consumer.kafka_topic, consumer.client_id
+        );
     }
 
     log::info!("Starting HTTP server on {}", server_addr);
@@ -244,42 +689,98 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .route("/health", web::get().to(health_check));
 
         // Register routes from pre-built handlers
-        for (path, topic, partition) in &endpoint_handlers {
-            let path = path.clone();
-            let topic = topic.clone();
-            let partition = *partition;
+        for (endpoint, validator, routes) in &endpoint_handlers {
+            let path = endpoint.path.clone();
+            let topic = endpoint.kafka_topic.clone();
+            let partition = endpoint.kafka_partition;
+            let reply_topic = endpoint.reply_topic.clone();
+            let reply_timeout_ms = endpoint.reply_timeout_ms.unwrap_or(5000);
+            let validator = validator.clone();
+            let forward_headers_allowlist = endpoint.forward_headers.clone().unwrap_or_default();
+            let key_source = endpoint.key_source.clone();
+            let route_by = endpoint.route_by.clone();
+            let routes = routes.clone();
+            let default_route = endpoint.default_route.clone();
             let producer_clone = Arc::clone(&producer);
+            let pending_replies = Arc::clone(&pending_replies);
+            let transaction_lock = Arc::clone(&transaction_lock);
+            let transactional = transactional;
 
             app = app.route(
                 &path,
-                web::post().to(move |body: web::Bytes| {
+                web::post().to(move |body: web::Bytes, req: actix_web::HttpRequest| {
                     let topic = topic.clone();
+                    let reply_topic = reply_topic.clone();
+                    let validator = validator.clone();
+                    let forward_headers_allowlist = forward_headers_allowlist.clone();
+                    let key_source = key_source.clone();
+                    let route_by = route_by.clone();
+                    let routes = routes.clone();
+                    let default_route = default_route.clone();
                     let producer = Arc::clone(&producer_clone);
+                    let pending_replies = Arc::clone(&pending_replies);
+                    let transaction_lock = Arc::clone(&transaction_lock);
                     async move {
-                        // Create Kafka record from request body
-                        let record = FutureRecord::to(&topic)
-                            .partition(partition)
-                            .payload(body.as_ref())
-                            .key(""); // Empty key for now; could be enhanced
-
-                        // Send message to Kafka
-                        match producer.send(record, Duration::from_secs(0)).await {
-                            Ok(delivery) => {
-                                log::info!("Message sent to topic {} partition {}: {:?}", topic, partition, delivery);
-                                HttpResponse::Ok().json(serde_json::json!({
-                                    "status": "success",
-                                    "message": "Message sent to Kafka",
-                                    "topic": topic,
-                                    "partition": partition
-                                }))
+                        if let Some(validator) = &validator {
+                            if let Some(response) = validate_body(validator, &body) {
+                                return response;
                             }
-                            Err((e, _)) => {
-                                log::error!("Failed to send message to Kafka topic {} partition {}: {:?}", topic, partition, e);
-                                HttpResponse::InternalServerError().json(serde_json::json!({
+                        }
+
+                        let (topic, partition) = match resolve_route(&req, body.as_ref(), &route_by, &routes, &default_route) {
+                            RouteResolution::Disabled => (topic, partition),
+                            RouteResolution::Target(topic, partition) => (topic, partition),
+                            RouteResolution::Unmatched => {
+                                return HttpResponse::UnprocessableEntity().json(serde_json::json!({
                                     "status": "error",
-                                    "message": "Failed to send message to Kafka",
-                                    "error": e.to_string()
-                                }))
+                                    "message": "No route matched the request and no default route is configured"
+                                }));
+                            }
+                        };
+
+                        let headers = forward_headers(&req, &forward_headers_allowlist);
+                        let key = derive_key(&req, body.as_ref(), &key_source);
+
+                        match &reply_topic {
+                            Some(_) => {
+                                handle_request_reply(
+                                    producer,
+                                    pending_replies,
+                                    topic,
+                                    partition,
+                                    key,
+                                    headers,
+                                    body,
+                                    reply_timeout_ms,
+                                    transactional,
+                                    transaction_lock,
+                                )
+                                .await
+                            }
+                            None => {
+                                // Create Kafka record from request body. When a key was derived,
+                                // let librdkafka's key-hash partitioner place the record instead
+                                // of pinning it to the endpoint's fixed partition.
+                                let mut record = FutureRecord::to(&topic).payload(body.as_ref()).headers(headers);
+                                record = match &key {
+                                    Some(key) => record.key(key.as_str()),
+                                    None => record.key("").partition(partition),
+                                };
+
+                                // Send message to Kafka, wrapping it in a transaction if configured
+                                match send_with_transaction(&producer, transactional, &transaction_lock, &topic, partition, record).await {
+                                    Ok((ack_partition, offset)) => {
+                                        log::info!("Message sent to topic {} partition {} offset {}", topic, ack_partition, offset);
+                                        HttpResponse::Ok().json(serde_json::json!({
+                                            "status": "success",
+                                            "message": "Message sent to Kafka",
+                                            "topic": topic,
+                                            "partition": ack_partition,
+                                            "offset": offset
+                                        }))
+                                    }
+                                    Err(response) => response,
+                                }
                             }
                         }
                     }
@@ -287,6 +788,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
         }
 
+        // Register the replay/drain GET endpoints
+        for consumer in &consumers {
+            let consumer = consumer.clone();
+            let kafka_servers = kafka_servers.clone();
+            let kafka_security = kafka_security.clone();
+
+            app = app.route(
+                &consumer.path.clone(),
+                web::get().to(move || {
+                    let consumer = consumer.clone();
+                    let kafka_servers = kafka_servers.clone();
+                    let kafka_security = kafka_security.clone();
+                    async move { handle_replay(kafka_servers, kafka_security, consumer).await }
+                }),
+            );
+        }
+
         app
     })
         .bind(&server_addr)?
@@ -296,6 +814,379 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Send `record`, wrapping it in a Kafka transaction when `transactional` is set.
+///
+/// Begins a transaction before producing and commits it after a successful send, aborting it
+/// if the send or the commit fails (a failed begin returns an error without aborting). Returns the delivered `(partition, offset)` on
+/// success, or an HTTP error response the caller should return as-is on failure.
+///
+/// `producer` is a single `FutureProducer` shared by every handler, and librdkafka's
+/// transactional API is a single state machine: begin/send/commit must never interleave across
+/// concurrent callers. When `transactional` is set, `transaction_lock` is held for the whole
+/// begin..commit/abort section so only one transaction is ever in flight on this producer.
+///
+/// `begin_transaction`/`commit_transaction`/`abort_transaction` are synchronous calls straight
+/// into librdkafka and can block the calling thread for up to their timeout, so each one runs on
+/// `spawn_blocking` to keep the actix worker free to service other requests while it waits.
+async fn send_with_transaction(
+    producer: &FutureProducer,
+    transactional: bool,
+    transaction_lock: &tokio::sync::Mutex<()>,
+    topic: &str,
+    partition: i32,
+    record: FutureRecord<'_, str, [u8]>,
+) -> Result<(i32, i64), HttpResponse> {
+    let _guard = if transactional {
+        Some(transaction_lock.lock().await)
+    } else {
+        None
+    };
+
+    if transactional {
+        if let Err(e) = blocking_transaction_call(producer, "begin", |p| p.begin_transaction()).await {
+            log::error!("Failed to begin Kafka transaction for topic {}: {}", topic, e);
+            return Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Failed to begin Kafka transaction",
+                "error": e
+            })));
+        }
+    }
+
+    match producer.send(record, Duration::from_secs(0)).await {
+        Ok(delivery) => {
+            if transactional {
+                if let Err(e) = blocking_transaction_call(producer, "commit", |p| {
+                    p.commit_transaction(Duration::from_secs(10))
+                })
+                .await
+                {
+                    log::error!("Failed to commit Kafka transaction for topic {}: {}", topic, e);
+                    let _ = blocking_transaction_call(producer, "abort", |p| {
+                        p.abort_transaction(Duration::from_secs(10))
+                    })
+                    .await;
+                    return Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                        "status": "error",
+                        "message": "Failed to commit Kafka transaction",
+                        "error": e
+                    })));
+                }
+            }
+            Ok(delivery)
+        }
+        Err((e, _)) => {
+            if transactional {
+                let _ = blocking_transaction_call(producer, "abort", |p| {
+                    p.abort_transaction(Duration::from_secs(10))
+                })
+                .await;
+            }
+            log::error!("Failed to send message to Kafka topic {} partition {}: {:?}", topic, partition, e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Failed to send message to Kafka",
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Run one of `Producer`'s blocking transaction-control calls (`begin`/`commit`/`abort`) on the
+/// blocking thread pool, since they block straight into librdkafka rather than returning a
+/// future. Returns the call's error message, or a message describing the blocking task itself
+/// panicking, as a plain `String` so callers don't need to know which case occurred.
+async fn blocking_transaction_call(
+    producer: &FutureProducer,
+    step: &str,
+    f: impl FnOnce(&FutureProducer) -> Result<(), rdkafka::error::KafkaError> + Send + 'static,
+) -> Result<(), String> {
+    let producer = producer.clone();
+    match tokio::task::spawn_blocking(move || f(&producer)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(join_err) => Err(format!("{} task panicked: {}", step, join_err)),
+    }
+}
+
+/// Produce `body` to `topic`/`partition` tagged with a fresh correlation id, then block until
+/// a matching reply arrives on the endpoint's reply topic or `reply_timeout_ms` elapses.
+///
+/// The correlation id's oneshot sender is registered in `pending_replies` before the record is
+/// produced, and always removed again before this function returns (success, timeout, or send
+/// failure) so the map never accumulates stale entries.
+async fn handle_request_reply(
+    producer: Arc<FutureProducer>,
+    pending_replies: PendingReplies,
+    topic: String,
+    partition: i32,
+    key: Option<String>,
+    headers: OwnedHeaders,
+    body: web::Bytes,
+    reply_timeout_ms: u64,
+    transactional: bool,
+    transaction_lock: Arc<tokio::sync::Mutex<()>>,
+) -> HttpResponse {
+    let correlation_id = Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    pending_replies.insert(correlation_id.clone(), tx);
+
+    let headers = headers.insert(rdkafka::message::Header {
+        key: CORRELATION_ID_HEADER,
+        value: Some(correlation_id.as_bytes()),
+    });
+    let mut record = FutureRecord::to(&topic).payload(body.as_ref()).headers(headers);
+    record = match &key {
+        Some(key) => record.key(key.as_str()),
+        None => record.key("").partition(partition),
+    };
+
+    if let Err(response) = send_with_transaction(&producer, transactional, &transaction_lock, &topic, partition, record).await {
+        pending_replies.remove(&correlation_id);
+        return response;
+    }
+
+    let reply = tokio::time::timeout(Duration::from_millis(reply_timeout_ms), rx).await;
+    pending_replies.remove(&correlation_id);
+
+    match reply {
+        Ok(Ok(payload)) => HttpResponse::Ok().body(payload),
+        Ok(Err(_)) => {
+            log::error!("Reply channel for correlation id {} dropped before a reply arrived", correlation_id);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Reply channel closed before a reply arrived"
+            }))
+        }
+        Err(_) => {
+            log::warn!("Timed out after {}ms waiting for a reply with correlation id {}", reply_timeout_ms, correlation_id);
+            HttpResponse::build(actix_web::http::StatusCode::GATEWAY_TIMEOUT).json(serde_json::json!({
+                "status": "error",
+                "message": "Timed out waiting for reply"
+            }))
+        }
+    }
+}
+
+/// Spawn a background task that consumes `reply_topic` and resolves pending request/reply
+/// correlation ids as matching messages arrive.
+///
+/// Each message's `correlation-id` header is looked up in `pending_replies`; a hit fires the
+/// waiting oneshot with the message payload. Messages with no matching entry (already timed
+/// out, or not a reply at all) are logged and dropped.
+///
+/// `instance_id` is mixed into the consumer group id so that separate collector processes never
+/// share a group for the same reply topic: `pending_replies` is only visible within this
+/// process, so a reply delivered to another instance's consumer would never reach the oneshot
+/// registered here and that request would time out instead.
+///
+/// Returns a oneshot that fires once this consumer has an initial partition assignment (or after
+/// a 10s warm-up timeout). Partition assignment happens lazily, inside the client's internal poll
+/// loop, the first time something drives it: a reply produced before that has completed would
+/// land on a partition this consumer isn't assigned to yet, and `auto.offset.reset: latest` means
+/// the assignment that follows starts after that message, so the reply would never be seen.
+/// Callers should wait on this before accepting HTTP traffic on endpoints using this reply topic.
+fn spawn_reply_consumer(
+    kafka_servers: &str,
+    kafka_security: &KafkaSecurityConfig,
+    reply_topic: String,
+    instance_id: Uuid,
+    pending_replies: PendingReplies,
+) -> Result<oneshot::Receiver<()>, Box<dyn std::error::Error>> {
+    let mut consumer_config = ClientConfig::new();
+    consumer_config
+        .set("bootstrap.servers", kafka_servers)
+        .set("group.id", format!("collector-reply-{}-{}", reply_topic, instance_id))
+        .set("enable.auto.commit", "true")
+        .set("auto.offset.reset", "latest");
+    kafka_security.apply(&mut consumer_config);
+
+    let consumer: StreamConsumer = consumer_config
+        .create()
+        .map_err(|e| format!("Failed to create reply consumer for topic {}: {}", reply_topic, e))?;
+
+    consumer.subscribe(&[reply_topic.as_str()])?;
+    let consumer = Arc::new(consumer);
+
+    let (ready_tx, ready_rx) = oneshot::channel();
+    {
+        let consumer = Arc::clone(&consumer);
+        let reply_topic = reply_topic.clone();
+        tokio::spawn(async move {
+            let mut ready_tx = Some(ready_tx);
+            let warmup_deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+            loop {
+                let assigned = consumer.assignment().map(|tpl| !tpl.elements().is_empty()).unwrap_or(false);
+                if assigned {
+                    break;
+                }
+                if tokio::time::Instant::now() >= warmup_deadline {
+                    log::warn!(
+                        "Reply consumer for topic {} had no partition assignment after 10s; starting HTTP server anyway",
+                        reply_topic
+                    );
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            if let Some(tx) = ready_tx.take() {
+                let _ = tx.send(());
+            }
+        });
+    }
+
+    log::info!("Listening for replies on topic {}", reply_topic);
+    tokio::spawn(async move {
+        loop {
+            match consumer.recv().await {
+                Ok(message) => {
+                    let Some(correlation_id) = find_correlation_id(message.headers()) else {
+                        log::warn!("Reply on topic {} had no {} header; dropping", reply_topic, CORRELATION_ID_HEADER);
+                        continue;
+                    };
+
+                    if let Some((_, sender)) = pending_replies.remove(&correlation_id) {
+                        let payload = message.payload().unwrap_or(&[]).to_vec();
+                        if sender.send(payload).is_err() {
+                            log::warn!("Reply for correlation id {} arrived after its requester stopped waiting", correlation_id);
+                        }
+                    } else {
+                        log::warn!("No pending request for correlation id {} on topic {}; dropping reply", correlation_id, reply_topic);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Error consuming replies from topic {}: {}", reply_topic, e);
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+            }
+        }
+    });
+
+    Ok(ready_rx)
+}
+
+/// Drain up to `consumer.max_messages` off `consumer.kafka_topic`, polling for up to
+/// `consumer.poll_timeout_ms` before returning whatever was collected. Every call replays the
+/// whole topic from the beginning; this is a full-replay tool, not an incremental drain.
+///
+/// Each call creates a short-lived `StreamConsumer` and tears it down once the request completes;
+/// this is a debugging/replay tool, not a long-running subscription. Rather than `subscribe()`,
+/// which would join a consumer group, every partition of the topic is fetched from cluster
+/// metadata and assigned directly with `assign()`: a replay always wants every partition from the
+/// beginning, never needs rebalancing, and group membership would leave behind empty,
+/// never-cleaned-up group metadata on the brokers after every request. `consumer.client_id` is
+/// still passed as `group.id` because librdkafka requires it to construct a consumer, but no
+/// offsets are ever committed or tracked against it, so repeated calls never advance.
+///
+/// Payloads are base64-encoded rather than UTF-8 decoded, since endpoints accept arbitrary bytes
+/// (protobuf, Avro, msgpack, ...) and a lossy decode would corrupt anything that isn't valid
+/// UTF-8.
+async fn handle_replay(kafka_servers: String, kafka_security: KafkaSecurityConfig, consumer: ConsumerConfig) -> HttpResponse {
+    let mut consumer_config = ClientConfig::new();
+    consumer_config
+        .set("bootstrap.servers", &kafka_servers)
+        .set("group.id", &consumer.client_id)
+        .set("enable.auto.commit", "false");
+    kafka_security.apply(&mut consumer_config);
+
+    let stream_consumer: StreamConsumer = match consumer_config.create() {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to create replay consumer for topic {}: {}", consumer.kafka_topic, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Failed to create Kafka consumer",
+                "error": e.to_string()
+            }));
+        }
+    };
+
+    let metadata_timeout = Duration::from_millis(consumer.poll_timeout_ms);
+    let partitions = match stream_consumer.fetch_metadata(Some(&consumer.kafka_topic), metadata_timeout) {
+        Ok(metadata) => match metadata.topics().iter().find(|t| t.name() == consumer.kafka_topic) {
+            Some(topic_metadata) => topic_metadata.partitions().iter().map(|p| p.id()).collect::<Vec<_>>(),
+            None => Vec::new(),
+        },
+        Err(e) => {
+            log::error!("Failed to fetch metadata for topic {}: {}", consumer.kafka_topic, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Failed to fetch Kafka topic metadata",
+                "error": e.to_string()
+            }));
+        }
+    };
+
+    if partitions.is_empty() {
+        log::error!("Topic {} has no partitions (it may not exist)", consumer.kafka_topic);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Kafka topic has no partitions",
+            "topic": consumer.kafka_topic
+        }));
+    }
+
+    let mut assignment = TopicPartitionList::new();
+    for partition in partitions {
+        assignment.add_partition_offset(&consumer.kafka_topic, partition, Offset::Beginning).expect("offset is always valid");
+    }
+
+    if let Err(e) = stream_consumer.assign(&assignment) {
+        log::error!("Failed to assign partitions for topic {}: {}", consumer.kafka_topic, e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Failed to assign Kafka partitions",
+            "error": e.to_string()
+        }));
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(consumer.poll_timeout_ms);
+    let mut messages = Vec::new();
+    let mut poll_error = None;
+    while messages.len() < consumer.max_messages {
+        let Some(remaining) = deadline.checked_duration_since(tokio::time::Instant::now()) else {
+            break;
+        };
+        match tokio::time::timeout(remaining, stream_consumer.recv()).await {
+            Ok(Ok(message)) => {
+                messages.push(serde_json::json!({
+                    "partition": message.partition(),
+                    "offset": message.offset(),
+                    "timestamp": message.timestamp().to_millis(),
+                    "payload": message.payload().map(|p| BASE64.encode(p))
+                }));
+            }
+            Ok(Err(e)) => {
+                log::error!("Error polling topic {} for replay: {}", consumer.kafka_topic, e);
+                poll_error = Some(e.to_string());
+                break;
+            }
+            Err(_) => break, // poll_timeout_ms elapsed
+        }
+    }
+
+    if let Some(e) = poll_error {
+        log::info!("Replay of topic {} aborted after {} message(s) due to poll error", consumer.kafka_topic, messages.len());
+        return HttpResponse::BadGateway().json(serde_json::json!({
+            "status": "error",
+            "message": "Error polling Kafka during replay",
+            "topic": consumer.kafka_topic,
+            "error": e,
+            "truncated": true,
+            "count": messages.len(),
+            "messages": messages
+        }));
+    }
+
+    log::info!("Replayed {} message(s) from topic {}", messages.len(), consumer.kafka_topic);
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "topic": consumer.kafka_topic,
+        "count": messages.len(),
+        "messages": messages
+    }))
+}
+
 /// Initialize logging based on daemon mode and configuration.
 ///
 /// In normal mode, logs are written to stdout/stderr using env_logger.
@@ -375,4 +1266,298 @@ fn daemonize_process(args: &Args, service_user: &str, service_group: &str) -> Re
             Err(e.into())
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_correlation_id_reads_matching_header() {
+        let headers = OwnedHeaders::new().insert(rdkafka::message::Header {
+            key: CORRELATION_ID_HEADER,
+            value: Some(b"abc-123".as_slice()),
+        });
+
+        assert_eq!(find_correlation_id(Some(&headers)), Some("abc-123".to_string()));
+    }
+
+    #[test]
+    fn find_correlation_id_ignores_other_headers() {
+        let headers = OwnedHeaders::new().insert(rdkafka::message::Header {
+            key: "x-request-id",
+            value: Some(b"not-it".as_slice()),
+        });
+
+        assert_eq!(find_correlation_id(Some(&headers)), None);
+    }
+
+    #[test]
+    fn find_correlation_id_handles_no_headers() {
+        assert_eq!(find_correlation_id::<OwnedHeaders>(None), None);
+    }
+
+    /// Compile `schema_json` the same way [`compile_schema`] does, minus the file read.
+    fn compiled(schema_json: serde_json::Value) -> CompiledSchema {
+        let schema: &'static serde_json::Value = Box::leak(Box::new(schema_json));
+        JSONSchema::compile(schema).expect("valid schema")
+    }
+
+    #[test]
+    fn validate_body_accepts_a_conforming_payload() {
+        let validator = compiled(serde_json::json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": { "id": { "type": "string" } }
+        }));
+
+        let body = web::Bytes::from_static(br#"{"id": "abc"}"#);
+        assert!(validate_body(&validator, &body).is_none());
+    }
+
+    #[test]
+    fn validate_body_rejects_non_json() {
+        let validator = compiled(serde_json::json!({ "type": "object" }));
+
+        let body = web::Bytes::from_static(b"not json");
+        let response = validate_body(&validator, &body).expect("non-JSON body should be rejected");
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn validate_body_rejects_a_schema_violation() {
+        let validator = compiled(serde_json::json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": { "id": { "type": "string" } }
+        }));
+
+        let body = web::Bytes::from_static(b"{}");
+        let response = validate_body(&validator, &body).expect("missing required field should be rejected");
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    }
+
+    fn header_values(headers: &OwnedHeaders) -> Vec<(&str, &[u8])> {
+        headers.iter().map(|h| (h.key, h.value.unwrap_or(&[]))).collect()
+    }
+
+    #[test]
+    fn forward_headers_copies_only_allowlisted_headers_present_on_the_request() {
+        let req = actix_web::test::TestRequest::default()
+            .insert_header(("x-tenant", "acme"))
+            .insert_header(("x-ignored", "nope"))
+            .to_http_request();
+
+        let headers = forward_headers(&req, &["x-tenant".to_string(), "x-missing".to_string()]);
+
+        assert_eq!(header_values(&headers), vec![("x-tenant", b"acme".as_slice())]);
+    }
+
+    #[test]
+    fn forward_headers_returns_empty_headers_for_an_empty_allowlist() {
+        let req = actix_web::test::TestRequest::default()
+            .insert_header(("x-tenant", "acme"))
+            .to_http_request();
+
+        let headers = forward_headers(&req, &[]);
+
+        assert_eq!(headers.count(), 0);
+    }
+
+    #[test]
+    fn derive_key_reads_from_a_header_source() {
+        let req = actix_web::test::TestRequest::default().insert_header(("x-tenant", "acme")).to_http_request();
+        let key_source = Some(KeySource::Header("x-tenant".to_string()));
+
+        assert_eq!(derive_key(&req, b"{}", &key_source), Some("acme".to_string()));
+    }
+
+    #[test]
+    fn derive_key_reads_from_a_json_pointer_source() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let key_source = Some(KeySource::JsonPointer("/tenant/id".to_string()));
+
+        assert_eq!(
+            derive_key(&req, br#"{"tenant": {"id": "acme"}}"#, &key_source),
+            Some("acme".to_string())
+        );
+    }
+
+    #[test]
+    fn derive_key_returns_none_when_no_key_source_is_configured() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        assert_eq!(derive_key(&req, b"{}", &None), None);
+    }
+
+    #[test]
+    fn derive_key_returns_none_when_the_json_pointer_misses() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let key_source = Some(KeySource::JsonPointer("/missing".to_string()));
+
+        assert_eq!(derive_key(&req, b"{}", &key_source), None);
+    }
+
+    #[test]
+    fn endpoint_config_parses_a_header_key_source() {
+        let endpoint: EndpointConfig = serde_yaml::from_str(
+            r#"
+path: /api/v1/events
+kafka_topic: events
+kafka_partition: 0
+key_source:
+  Header: x-tenant
+"#,
+        )
+        .expect("valid endpoint config");
+
+        assert!(matches!(endpoint.key_source, Some(KeySource::Header(ref h)) if h == "x-tenant"));
+    }
+
+    #[test]
+    fn endpoint_config_parses_a_json_pointer_key_source() {
+        let endpoint: EndpointConfig = serde_yaml::from_str(
+            r#"
+path: /api/v1/events
+kafka_topic: events
+kafka_partition: 0
+key_source:
+  JsonPointer: /tenant/id
+"#,
+        )
+        .expect("valid endpoint config");
+
+        assert!(matches!(endpoint.key_source, Some(KeySource::JsonPointer(ref p)) if p == "/tenant/id"));
+    }
+
+    fn route_target(topic: &str, partition: i32) -> RouteTarget {
+        RouteTarget { kafka_topic: topic.to_string(), kafka_partition: partition }
+    }
+
+    #[test]
+    fn resolve_route_is_disabled_without_route_by() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        assert_eq!(resolve_route(&req, b"{}", &None, &None, &None), RouteResolution::Disabled);
+    }
+
+    #[test]
+    fn resolve_route_prefers_the_header_over_the_body() {
+        let req = actix_web::test::TestRequest::default().insert_header(("event-type", "orders")).to_http_request();
+        let route_by = Some("event-type".to_string());
+        let routes = Some(Arc::new(HashMap::from([
+            ("orders".to_string(), route_target("orders-topic", 0)),
+            ("carts".to_string(), route_target("carts-topic", 0)),
+        ])));
+
+        let resolution = resolve_route(&req, br#"{"event-type": "carts"}"#, &route_by, &routes, &None);
+
+        assert_eq!(resolution, RouteResolution::Target("orders-topic".to_string(), 0));
+    }
+
+    #[test]
+    fn resolve_route_falls_back_to_the_body_field() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let route_by = Some("event_type".to_string());
+        let routes = Some(Arc::new(HashMap::from([("carts".to_string(), route_target("carts-topic", 2))])));
+
+        let resolution = resolve_route(&req, br#"{"event_type": "carts"}"#, &route_by, &routes, &None);
+
+        assert_eq!(resolution, RouteResolution::Target("carts-topic".to_string(), 2));
+    }
+
+    #[test]
+    fn resolve_route_uses_the_default_route_when_unmatched() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let route_by = Some("event_type".to_string());
+        let routes = Some(Arc::new(HashMap::from([("carts".to_string(), route_target("carts-topic", 0))])));
+        let default_route = Some(route_target("default-topic", 1));
+
+        let resolution = resolve_route(&req, br#"{"event_type": "unknown"}"#, &route_by, &routes, &default_route);
+
+        assert_eq!(resolution, RouteResolution::Target("default-topic".to_string(), 1));
+    }
+
+    #[test]
+    fn resolve_route_is_unmatched_without_a_default_route() {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let route_by = Some("event_type".to_string());
+        let routes = Some(Arc::new(HashMap::from([("carts".to_string(), route_target("carts-topic", 0))])));
+
+        let resolution = resolve_route(&req, br#"{"event_type": "unknown"}"#, &route_by, &routes, &None);
+
+        assert_eq!(resolution, RouteResolution::Unmatched);
+    }
+
+    #[test]
+    fn endpoint_config_parses_routes_and_default_route() {
+        let endpoint: EndpointConfig = serde_yaml::from_str(
+            r#"
+path: /api/v1/events
+kafka_topic: events
+kafka_partition: 0
+route_by: event-type
+routes:
+  orders:
+    kafka_topic: orders-topic
+    kafka_partition: 0
+  carts:
+    kafka_topic: carts-topic
+    kafka_partition: 2
+default_route:
+  kafka_topic: default-topic
+  kafka_partition: 1
+"#,
+        )
+        .expect("valid endpoint config");
+
+        let routes = endpoint.routes.expect("routes should be present");
+        assert_eq!(routes.get("orders").map(|r| (r.kafka_topic.as_str(), r.kafka_partition)), Some(("orders-topic", 0)));
+        assert_eq!(routes.get("carts").map(|r| (r.kafka_topic.as_str(), r.kafka_partition)), Some(("carts-topic", 2)));
+
+        let default_route = endpoint.default_route.expect("default_route should be present");
+        assert_eq!(default_route.kafka_topic, "default-topic");
+        assert_eq!(default_route.kafka_partition, 1);
+    }
+
+    #[test]
+    fn kafka_security_config_apply_sets_only_the_configured_keys() {
+        let security = KafkaSecurityConfig {
+            security_protocol: Some("SASL_SSL".to_string()),
+            sasl_mechanism: Some("PLAIN".to_string()),
+            sasl_username: Some("alice".to_string()),
+            sasl_password: Some("hunter2".to_string()),
+            ssl_ca_location: None,
+            ssl_certificate_location: None,
+            ssl_key_location: None,
+            ssl_key_password: None,
+        };
+
+        let mut client_config = ClientConfig::new();
+        security.apply(&mut client_config);
+
+        assert_eq!(client_config.get("security.protocol"), Some("SASL_SSL"));
+        assert_eq!(client_config.get("sasl.mechanisms"), Some("PLAIN"));
+        assert_eq!(client_config.get("sasl.username"), Some("alice"));
+        assert_eq!(client_config.get("sasl.password"), Some("hunter2"));
+        assert_eq!(client_config.get("ssl.ca.location"), None);
+        assert_eq!(client_config.get("ssl.certificate.location"), None);
+        assert_eq!(client_config.get("ssl.key.location"), None);
+        assert_eq!(client_config.get("ssl.key.password"), None);
+    }
+
+    #[test]
+    fn kafka_security_config_apply_is_a_no_op_when_unset() {
+        let security = KafkaSecurityConfig::default();
+
+        let mut client_config = ClientConfig::new();
+        security.apply(&mut client_config);
+
+        assert_eq!(client_config.get("security.protocol"), None);
+        assert_eq!(client_config.get("sasl.mechanisms"), None);
+        assert_eq!(client_config.get("sasl.username"), None);
+        assert_eq!(client_config.get("sasl.password"), None);
+        assert_eq!(client_config.get("ssl.ca.location"), None);
+        assert_eq!(client_config.get("ssl.certificate.location"), None);
+        assert_eq!(client_config.get("ssl.key.location"), None);
+        assert_eq!(client_config.get("ssl.key.password"), None);
+    }
 }
\ No newline at end of file